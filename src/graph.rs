@@ -1,8 +1,181 @@
 
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
 use std::fmt::{Display, Result, Formatter};
 
 
+// Magic number identifying the binary graph format.
+const GRAPH_MAGIC: u32 = 0x7ae7_1ffd;
+// Current on-disk format version.
+const GRAPH_VERSION: u32 = 1;
+
+// Errors returned by graph queries and by decoding the binary format.
+#[derive(Debug, PartialEq)]
+enum GraphError {
+    NegativeWeight,
+    BadMagic(u32),
+    UnsupportedVersion(u32),
+    Truncated,
+    InvalidVertex(usize),
+}
+
+// Cursor over a byte slice for decoding the binary format.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> ByteReader<'a> {
+        ByteReader { bytes, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> std::result::Result<&'a [u8], GraphError> {
+        if self.pos + n > self.bytes.len() {
+            return Err(GraphError::Truncated);
+        }
+        let slice = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> std::result::Result<u32, GraphError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> std::result::Result<u64, GraphError> {
+        let b = self.take(8)?;
+        Ok(u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+    }
+
+    fn i64(&mut self) -> std::result::Result<i64, GraphError> {
+        Ok(self.u64()? as i64)
+    }
+
+    fn f64(&mut self) -> std::result::Result<f64, GraphError> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+
+    fn u8(&mut self) -> std::result::Result<u8, GraphError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn string(&mut self) -> std::result::Result<String, GraphError> {
+        let len = self.u64()? as usize;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| GraphError::Truncated)
+    }
+}
+
+// Escape characters that would otherwise terminate a DOT quoted string.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Append a length-prefixed UTF-8 string.
+fn write_string(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(&(s.len() as u64).to_le_bytes());
+    buf.extend_from_slice(s.as_bytes());
+}
+
+// Frontier result of `explore`: tentative distances and predecessor map.
+type Explored = (HashMap<usize, f64>, HashMap<usize, usize>);
+
+// Heap entry carrying both the tentative g-distance and the priority `f`
+// (`g + heuristic`). The heap is ordered on `f` so that `BinaryHeap`
+// (a max-heap) pops the least-priority entry.
+#[derive(Debug)]
+struct State {
+    g: f64,
+    f: f64,
+    vertex: usize,
+}
+
+impl PartialEq for State {
+    fn eq(&self, other: &State) -> bool {
+        self.f == other.f && self.vertex == other.vertex
+    }
+}
+
+impl Eq for State {}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &State) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for State {
+    fn cmp(&self, other: &State) -> Ordering {
+        // reversed so the least-priority entry sorts highest in the max-heap
+        other.f.total_cmp(&self.f).then_with(|| self.vertex.cmp(&other.vertex))
+    }
+}
+
+// Compressed Sparse Row view of the adjacency matrix: `row_ptr[i]..row_ptr[i+1]`
+// indexes into `col_idx`/`values` for row `i`.
+#[derive(Debug)]
+struct CsrMatrix {
+    n: usize,
+    row_ptr: Vec<usize>,
+    col_idx: Vec<usize>,
+    values: Vec<f64>,
+}
+
+// A block that survived compression, recorded by its block coordinates and the
+// edge density of its in-bounds cells (boundary blocks can be smaller than
+// `block_size x block_size`).
+#[derive(Debug)]
+struct CompressedBlock {
+    row: usize,
+    col: usize,
+    density: f64,
+}
+
+// Lossy, block-wise approximation of a graph's adjacency matrix.
+#[derive(Debug)]
+struct CompressedGraph {
+    n: usize,
+    is_directed: bool,
+    block_size: usize,
+    blocks: Vec<CompressedBlock>,
+}
+
+impl CompressedGraph {
+    // Reconstruct an approximate graph, treating every cell of a surviving
+    // block as a present edge.
+    pub fn decompress(&self) -> Graph {
+        let mut g = Graph::new(self.is_directed);
+        for _ in 0..self.n {
+            g.add_vertex();
+        }
+        for block in &self.blocks {
+            let r0 = block.row * self.block_size;
+            let c0 = block.col * self.block_size;
+            for i in r0..(r0 + self.block_size).min(self.n) {
+                for j in c0..(c0 + self.block_size).min(self.n) {
+                    // for undirected graphs a single logical edge covers both
+                    // triangular cells, so only add it once
+                    if self.is_directed || i <= j {
+                        g.add_edge(i, j);
+                    }
+                }
+            }
+        }
+        g
+    }
+}
+
+// Classic three-colour marking used by the depth-first traversal: White is
+// undiscovered, Gray is on the current recursion stack, Black is finished.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Color {
+    White,
+    Gray,
+    Black,
+}
+
 // Adjacency list graph
 #[derive(Debug)]
 struct Vertex {
@@ -12,20 +185,27 @@ struct Vertex {
     pub float_props: HashMap<String, f64>
 }
 
-// struct Edge {
-//     pub weight: f64,
-//     pub source: u64,
-//     pub sink: u64,
-//     pub string_props: HashMap<String, String>,
-//     pub int_props: HashMap<String, i64>,
-//     pub float_props: HashMap<String, f64>
-// }
+#[derive(Debug)]
+struct Edge {
+    pub id: usize,
+    pub source: usize,
+    pub sink: usize,
+    pub weight: f64,
+    pub string_props: HashMap<String, String>,
+    pub int_props: HashMap<String, i64>,
+    pub float_props: HashMap<String, f64>
+}
 
 #[derive(Debug)]
 struct Graph {
     pub is_directed: bool,
-    pub vertices: Vec<Vertex>,
-    pub adjacent: Vec<Vec<usize>>
+    // `None` entries are tombstones for removed vertices; their ids are reused
+    // from `free_list` so previously handed-out ids stay valid.
+    pub vertices: Vec<Option<Vertex>>,
+    pub edges: Vec<Edge>,
+    // adjacency now stores edge ids into `edges` rather than bare target ids
+    pub adjacent: Vec<Vec<usize>>,
+    free_list: Vec<usize>
 }
 
 impl Vertex {
@@ -57,16 +237,60 @@ impl Display for Vertex {
     }
 }
 
+impl Edge {
+    pub fn new(id: usize, source: usize, sink: usize, weight: f64) -> Edge {
+        Edge {
+            id,
+            source,
+            sink,
+            weight,
+            string_props: HashMap::new(),
+            int_props: HashMap::new(),
+            float_props: HashMap::new()
+        }
+    }
+
+    pub fn add_string_props(&mut self, label: String, value: String) {
+        self.string_props.insert(label, value);
+    }
+
+    pub fn add_int_props(&mut self, label: String, value: i64) {
+        self.int_props.insert(label, value);
+    }
+
+    pub fn add_float_props(&mut self, label: String, value: f64) {
+        self.float_props.insert(label, value);
+    }
+
+    // The endpoint of this edge that is not `id`.
+    pub fn other(&self, id: usize) -> usize {
+        if self.source == id { self.sink } else { self.source }
+    }
+}
+
+impl Display for Edge {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "Edge(id: {}, {} -> {}, weight: {})", self.id, self.source, self.sink, self.weight)
+    }
+}
+
 impl Graph {
     pub fn new(is_directed: bool) -> Graph {
         Graph {
             is_directed,
             vertices: vec![],
-            adjacent: vec![]
+            edges: vec![],
+            adjacent: vec![],
+            free_list: vec![]
         }
     }
 
     pub fn num_vertices(&self) -> usize {
+        self.vertices.iter().filter(|v| v.is_some()).count()
+    }
+
+    // Number of vertex slots, including tombstones; slots double as vertex ids.
+    fn capacity(&self) -> usize {
         self.vertices.len()
     }
 
@@ -75,29 +299,582 @@ impl Graph {
     }
 
     pub fn get_vertex(&self, id: usize) -> Option<&Vertex> {
-        self.vertices.get(id)
+        self.vertices.get(id).and_then(|v| v.as_ref())
     }
 
+    pub fn get_edge(&self, id: usize) -> Option<&Edge> {
+        self.edges.get(id)
+    }
+
+    // Returns the ids of the edges incident to `id` (indices into `edges`),
+    // not vertex ids; `neighbors` resolves them to vertex ids.
     pub fn get_adjacent_vertices(&self, id: usize) -> Option<&Vec<usize>> {
         self.adjacent.get(id)
     }
 
+    // Resolve the adjacency of `id` into the neighbouring vertex ids.
+    pub fn neighbors(&self, id: usize) -> Vec<usize> {
+        match self.adjacent.get(id) {
+            Some(edges) => edges.iter().map(|&e| self.edges[e].other(id)).collect(),
+            None => vec![]
+        }
+    }
+
+    // Weight of the edge joining `from` and `to`, if one exists.
+    pub fn edge_weight(&self, from: usize, to: usize) -> Option<f64> {
+        let edges = self.adjacent.get(from)?;
+        for &e in edges {
+            let edge = &self.edges[e];
+            if edge.other(from) == to {
+                return Some(edge.weight);
+            }
+        }
+        None
+    }
+
     pub fn add_vertex(&mut self) -> usize {
-        let n = self.num_vertices();
-        let v = Vertex::new(n);
-        self.vertices.push(v);
+        // reuse a tombstoned slot before growing, so ids stay dense
+        if let Some(slot) = self.free_list.pop() {
+            self.vertices[slot] = Some(Vertex::new(slot));
+            self.adjacent[slot].clear();
+            return slot;
+        }
+        let n = self.vertices.len();
+        self.vertices.push(Some(Vertex::new(n)));
         // add empty adjacency list
         self.adjacent.push(Vec::new());
         return n
     }
 
+    // Remove the edge joining `from` and `to`, pruning it from both adjacency
+    // lists (in both directions for undirected graphs).
+    pub fn remove_edge(&mut self, from: usize, to: usize) {
+        let mut drop = vec![];
+        for &e in &self.adjacent[from] {
+            let edge = &self.edges[e];
+            let matches = if self.is_directed {
+                edge.source == from && edge.sink == to
+            } else {
+                edge.other(from) == to
+            };
+            if matches {
+                drop.push(e);
+            }
+        }
+        self.drop_edges(&drop);
+    }
+
+    // Remove a vertex, tombstoning its slot and pruning every incident edge.
+    // The id is parked on the free list for reuse by a later `add_vertex`.
+    pub fn remove_vertex(&mut self, id: usize) {
+        if self.get_vertex(id).is_none() {
+            return;
+        }
+        let drop: Vec<usize> = self
+            .edges
+            .iter()
+            .filter(|e| e.source == id || e.sink == id)
+            .map(|e| e.id)
+            .collect();
+        self.drop_edges(&drop);
+
+        self.vertices[id] = None;
+        self.adjacent[id].clear();
+        self.free_list.push(id);
+    }
+
+    // Remove the given edge ids from the arena and repair every edge-index
+    // reference in the adjacency lists. Compacting the arena renumbers the
+    // surviving edges, so edge ids returned earlier by `add_weighted_edge` may
+    // no longer refer to the same edge.
+    fn drop_edges(&mut self, drop: &[usize]) {
+        if drop.is_empty() {
+            return;
+        }
+        let mut remap = HashMap::new();
+        let mut kept = Vec::new();
+        for (old, mut edge) in self.edges.drain(..).enumerate() {
+            if drop.contains(&old) {
+                continue;
+            }
+            let new = kept.len();
+            edge.id = new;
+            remap.insert(old, new);
+            kept.push(edge);
+        }
+        self.edges = kept;
+        for list in &mut self.adjacent {
+            list.retain(|e| !drop.contains(e));
+            for e in list.iter_mut() {
+                *e = remap[&*e];
+            }
+        }
+    }
+
     pub fn add_edge(&mut self, from: usize, to:usize) {
-        self.adjacent[from].push(to);
+        self.add_weighted_edge(from, to, 1.0);
+    }
+
+    // Create a single logical `Edge` and register it with both endpoints for
+    // undirected graphs. Returns the id of the new edge in the `edges` arena.
+    pub fn add_weighted_edge(&mut self, from: usize, to: usize, weight: f64) -> usize {
+        let id = self.edges.len();
+        self.edges.push(Edge::new(id, from, to, weight));
+        self.adjacent[from].push(id);
 
         if !self.is_directed {
-            self.adjacent[to].push(from);
+            self.adjacent[to].push(id);
+        }
+        id
+    }
+
+
+    // Core Dijkstra/A* loop. `h` is the heuristic added to the tentative
+    // distance to order the frontier (the zero closure yields plain Dijkstra).
+    // When `target` is given the search stops as soon as it is finalized.
+    fn explore(
+        &self,
+        source: usize,
+        target: Option<usize>,
+        h: &dyn Fn(usize) -> f64,
+    ) -> std::result::Result<Explored, GraphError> {
+        if self.edges.iter().any(|e| e.weight < 0.0) {
+            return Err(GraphError::NegativeWeight);
+        }
+
+        let mut dist: HashMap<usize, f64> = HashMap::new();
+        let mut prev: HashMap<usize, usize> = HashMap::new();
+        for v in self.vertices.iter().flatten() {
+            dist.insert(v.id, f64::INFINITY);
+        }
+        dist.insert(source, 0.0);
+
+        let mut heap = BinaryHeap::new();
+        heap.push(State { g: 0.0, f: h(source), vertex: source });
+
+        while let Some(State { g, vertex: u, .. }) = heap.pop() {
+            // a stale entry: compare the carried g-distance directly rather
+            // than reconstructing it from the priority by subtraction
+            if g > dist[&u] {
+                continue;
+            }
+            if Some(u) == target {
+                break;
+            }
+
+            for &e in &self.adjacent[u] {
+                let edge = &self.edges[e];
+                let v = edge.other(u);
+                let next = dist[&u] + edge.weight;
+                if next < dist[&v] {
+                    dist.insert(v, next);
+                    prev.insert(v, u);
+                    heap.push(State { g: next, f: next + h(v), vertex: v });
+                }
+            }
+        }
+
+        Ok((dist, prev))
+    }
+
+    // Reconstruct the path source -> target from a predecessor map.
+    fn reconstruct(&self, source: usize, target: usize, prev: &HashMap<usize, usize>) -> Vec<usize> {
+        let mut path = vec![target];
+        let mut at = target;
+        while at != source {
+            match prev.get(&at) {
+                Some(&p) => {
+                    path.push(p);
+                    at = p;
+                }
+                None => return vec![],
+            }
+        }
+        path.reverse();
+        path
+    }
+
+    // Shortest-path distance from `source` to every vertex via Dijkstra.
+    pub fn shortest_paths(&self, source: usize) -> std::result::Result<HashMap<usize, f64>, GraphError> {
+        let (dist, _) = self.explore(source, None, &|_| 0.0)?;
+        Ok(dist)
+    }
+
+    // Shortest path and its total weight from `source` to `target`, if reachable.
+    pub fn shortest_path(
+        &self,
+        source: usize,
+        target: usize,
+    ) -> std::result::Result<Option<(Vec<usize>, f64)>, GraphError> {
+        let (dist, prev) = self.explore(source, Some(target), &|_| 0.0)?;
+        match dist.get(&target) {
+            Some(&d) if d.is_finite() => Ok(Some((self.reconstruct(source, target, &prev), d))),
+            _ => Ok(None),
+        }
+    }
+
+    // A* search: like `shortest_path` but guided by the heuristic `h`, which
+    // must never overestimate the remaining distance to `target`.
+    pub fn astar(
+        &self,
+        source: usize,
+        target: usize,
+        h: impl Fn(usize) -> f64,
+    ) -> std::result::Result<Option<(Vec<usize>, f64)>, GraphError> {
+        let (dist, prev) = self.explore(source, Some(target), &h)?;
+        match dist.get(&target) {
+            Some(&d) if d.is_finite() => Ok(Some((self.reconstruct(source, target, &prev), d))),
+            _ => Ok(None),
         }
     }
+
+
+    // Breadth-first visitation order from `start`, using a queue and a visited map.
+    pub fn bfs(&self, start: usize) -> Vec<usize> {
+        let mut order = vec![];
+        let mut visited = vec![false; self.capacity()];
+        let mut queue = VecDeque::new();
+
+        visited[start] = true;
+        queue.push_back(start);
+        while let Some(u) = queue.pop_front() {
+            order.push(u);
+            for v in self.neighbors(u) {
+                if !visited[v] {
+                    visited[v] = true;
+                    queue.push_back(v);
+                }
+            }
+        }
+        order
+    }
+
+    // Depth-first visitation order from `start` in discovery order.
+    pub fn dfs(&self, start: usize) -> Vec<usize> {
+        let mut order = vec![];
+        let mut color = vec![Color::White; self.capacity()];
+        self.dfs_visit(start, usize::MAX, &mut color, &mut order, &mut None);
+        order
+    }
+
+    // Visit `u` colouring it Gray on entry and Black on exit; record discovery
+    // order and, when `stack` is supplied, push finished vertices for the
+    // topological order. Returns true if a back edge (cycle) is seen.
+    fn dfs_visit(
+        &self,
+        u: usize,
+        parent: usize,
+        color: &mut Vec<Color>,
+        order: &mut Vec<usize>,
+        stack: &mut Option<Vec<usize>>,
+    ) -> bool {
+        color[u] = Color::Gray;
+        order.push(u);
+        let mut cyclic = false;
+
+        for v in self.neighbors(u) {
+            match color[v] {
+                Color::White => {
+                    if self.dfs_visit(v, u, color, order, stack) {
+                        cyclic = true;
+                    }
+                }
+                Color::Gray => {
+                    // a back edge signals a cycle; in an undirected graph the
+                    // edge straight back to the parent is not a cycle
+                    if self.is_directed || v != parent {
+                        cyclic = true;
+                    }
+                }
+                Color::Black => {}
+            }
+        }
+
+        color[u] = Color::Black;
+        if let Some(s) = stack.as_mut() {
+            s.push(u);
+        }
+        cyclic
+    }
+
+    // Whether the graph contains a cycle.
+    pub fn is_cyclic(&self) -> bool {
+        let mut color = vec![Color::White; self.capacity()];
+        let mut order = vec![];
+        let mut stack = None;
+        for v in self.vertices.iter().flatten() {
+            if color[v.id] == Color::White
+                && self.dfs_visit(v.id, usize::MAX, &mut color, &mut order, &mut stack)
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    // Topological ordering of the vertices, or `None` when a cycle exists.
+    pub fn topological_sort(&self) -> Option<Vec<usize>> {
+        let mut color = vec![Color::White; self.capacity()];
+        let mut order = vec![];
+        let mut stack = Some(vec![]);
+        for v in self.vertices.iter().flatten() {
+            if color[v.id] == Color::White
+                && self.dfs_visit(v.id, usize::MAX, &mut color, &mut order, &mut stack)
+            {
+                return None;
+            }
+        }
+        let mut sorted = stack.unwrap();
+        sorted.reverse();
+        Some(sorted)
+    }
+
+
+    // Render the graph as Graphviz DOT text for piping into `dot`.
+    pub fn to_dot(&self) -> String {
+        let (kind, arrow) = if self.is_directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+
+        let mut dot = format!("{} {{\n", kind);
+
+        for v in self.vertices.iter().flatten() {
+            let label = self.vertex_label(v);
+            if label.is_empty() {
+                dot.push_str(&format!("    {};\n", v.id));
+            } else {
+                dot.push_str(&format!("    {} [label=\"{}\"];\n", v.id, dot_escape(&label)));
+            }
+        }
+
+        for e in &self.edges {
+            dot.push_str(&format!("    {} {} {} [label=\"{}\"];\n", e.source, arrow, e.sink, e.weight));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    // Build a Compressed Sparse Row adjacency matrix for cache-friendly
+    // iteration and matrix-style algorithms.
+    pub fn to_csr(&self) -> CsrMatrix {
+        let n = self.capacity();
+        let mut row_ptr = vec![0usize; n + 1];
+        let mut col_idx = vec![];
+        let mut values = vec![];
+
+        for u in 0..n {
+            // collect this row's (column, weight) pairs and sort by column
+            let mut row: Vec<(usize, f64)> = self.adjacent[u]
+                .iter()
+                .map(|&e| (self.edges[e].other(u), self.edges[e].weight))
+                .collect();
+            row.sort_by_key(|&(c, _)| c);
+            for (c, w) in row {
+                col_idx.push(c);
+                values.push(w);
+            }
+            row_ptr[u + 1] = col_idx.len();
+        }
+
+        CsrMatrix { n, row_ptr, col_idx, values }
+    }
+
+    // Partition the NxN adjacency into `block_size` blocks, keeping only those
+    // whose edge density meets `threshold`.
+    pub fn compress(&self, block_size: usize, threshold: f64) -> CompressedGraph {
+        let n = self.capacity();
+
+        // dense presence matrix
+        let mut present = vec![vec![false; n]; n];
+        for (u, adj) in self.adjacent.iter().enumerate() {
+            for &e in adj {
+                present[u][self.edges[e].other(u)] = true;
+            }
+        }
+
+        let num_blocks = n.div_ceil(block_size);
+        let mut blocks = vec![];
+        for br in 0..num_blocks {
+            for bc in 0..num_blocks {
+                let row_end = ((br + 1) * block_size).min(n);
+                let col_end = ((bc + 1) * block_size).min(n);
+                let mut count = 0usize;
+                for i in (br * block_size)..row_end {
+                    for j in (bc * block_size)..col_end {
+                        if present[i][j] {
+                            count += 1;
+                        }
+                    }
+                }
+                // divide by the block's actual in-bounds cell count so that
+                // partial boundary blocks are measured fairly
+                let block_cells = ((row_end - br * block_size) * (col_end - bc * block_size)) as f64;
+                let density = count as f64 / block_cells;
+                if density >= threshold {
+                    blocks.push(CompressedBlock { row: br, col: bc, density });
+                }
+            }
+        }
+
+        CompressedGraph { n, is_directed: self.is_directed, block_size, blocks }
+    }
+
+    // Serialize the graph to a byte buffer with a versioned header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&GRAPH_MAGIC.to_le_bytes());
+        buf.extend_from_slice(&GRAPH_VERSION.to_le_bytes());
+        // record the full slot layout (including tombstones) so that the
+        // vertex ids carried by the edge block stay valid on reload
+        buf.extend_from_slice(&(self.capacity() as u64).to_le_bytes());
+        buf.extend_from_slice(&(self.edges.len() as u64).to_le_bytes());
+        buf.push(self.is_directed as u8);
+
+        for slot in &self.vertices {
+            match slot {
+                None => buf.push(0),
+                Some(v) => {
+                    buf.push(1);
+                    buf.extend_from_slice(&(v.id as u64).to_le_bytes());
+                    buf.extend_from_slice(&(v.string_props.len() as u64).to_le_bytes());
+                    for (k, val) in &v.string_props {
+                        write_string(&mut buf, k);
+                        write_string(&mut buf, val);
+                    }
+                    buf.extend_from_slice(&(v.int_props.len() as u64).to_le_bytes());
+                    for (k, val) in &v.int_props {
+                        write_string(&mut buf, k);
+                        buf.extend_from_slice(&val.to_le_bytes());
+                    }
+                    buf.extend_from_slice(&(v.float_props.len() as u64).to_le_bytes());
+                    for (k, val) in &v.float_props {
+                        write_string(&mut buf, k);
+                        buf.extend_from_slice(&val.to_bits().to_le_bytes());
+                    }
+                }
+            }
+        }
+
+        for e in &self.edges {
+            buf.extend_from_slice(&(e.source as u64).to_le_bytes());
+            buf.extend_from_slice(&(e.sink as u64).to_le_bytes());
+            buf.extend_from_slice(&e.weight.to_bits().to_le_bytes());
+            buf.extend_from_slice(&(e.string_props.len() as u64).to_le_bytes());
+            for (k, val) in &e.string_props {
+                write_string(&mut buf, k);
+                write_string(&mut buf, val);
+            }
+            buf.extend_from_slice(&(e.int_props.len() as u64).to_le_bytes());
+            for (k, val) in &e.int_props {
+                write_string(&mut buf, k);
+                buf.extend_from_slice(&val.to_le_bytes());
+            }
+            buf.extend_from_slice(&(e.float_props.len() as u64).to_le_bytes());
+            for (k, val) in &e.float_props {
+                write_string(&mut buf, k);
+                buf.extend_from_slice(&val.to_bits().to_le_bytes());
+            }
+        }
+
+        buf
+    }
+
+    // Reconstruct a graph from bytes produced by `to_bytes`, validating the
+    // magic number and version before decoding.
+    pub fn from_bytes(bytes: &[u8]) -> std::result::Result<Graph, GraphError> {
+        let mut r = ByteReader::new(bytes);
+        let magic = r.u32()?;
+        if magic != GRAPH_MAGIC {
+            return Err(GraphError::BadMagic(magic));
+        }
+        let version = r.u32()?;
+        if version != GRAPH_VERSION {
+            return Err(GraphError::UnsupportedVersion(version));
+        }
+        let capacity = r.u64()? as usize;
+        let edge_count = r.u64()? as usize;
+        let is_directed = r.u8()? != 0;
+
+        let mut g = Graph::new(is_directed);
+        // rebuild the slot layout directly so tombstones and ids are preserved
+        for slot in 0..capacity {
+            g.adjacent.push(Vec::new());
+            if r.u8()? == 0 {
+                g.vertices.push(None);
+                g.free_list.push(slot);
+                continue;
+            }
+            let id = r.u64()? as usize;
+            let mut v = Vertex::new(id);
+            let n = r.u64()? as usize;
+            for _ in 0..n {
+                let (k, val) = (r.string()?, r.string()?);
+                v.string_props.insert(k, val);
+            }
+            let n = r.u64()? as usize;
+            for _ in 0..n {
+                let k = r.string()?;
+                v.int_props.insert(k, r.i64()?);
+            }
+            let n = r.u64()? as usize;
+            for _ in 0..n {
+                let k = r.string()?;
+                v.float_props.insert(k, r.f64()?);
+            }
+            g.vertices.push(Some(v));
+        }
+
+        for _ in 0..edge_count {
+            let source = r.u64()? as usize;
+            let sink = r.u64()? as usize;
+            let weight = r.f64()?;
+            // guard against a corrupt payload referencing a missing vertex,
+            // which would otherwise panic inside `add_weighted_edge`
+            if g.get_vertex(source).is_none() {
+                return Err(GraphError::InvalidVertex(source));
+            }
+            if g.get_vertex(sink).is_none() {
+                return Err(GraphError::InvalidVertex(sink));
+            }
+            let id = g.add_weighted_edge(source, sink, weight);
+            let e = &mut g.edges[id];
+            let n = r.u64()? as usize;
+            for _ in 0..n {
+                let (k, val) = (r.string()?, r.string()?);
+                e.string_props.insert(k, val);
+            }
+            let n = r.u64()? as usize;
+            for _ in 0..n {
+                let k = r.string()?;
+                e.int_props.insert(k, r.i64()?);
+            }
+            let n = r.u64()? as usize;
+            for _ in 0..n {
+                let k = r.string()?;
+                e.float_props.insert(k, r.f64()?);
+            }
+        }
+
+        Ok(g)
+    }
+
+    // Build a `key=value` label string from a vertex's property maps.
+    fn vertex_label(&self, v: &Vertex) -> String {
+        let mut parts = vec![];
+        for (k, val) in &v.string_props {
+            parts.push(format!("{}={}", k, val));
+        }
+        for (k, val) in &v.int_props {
+            parts.push(format!("{}={}", k, val));
+        }
+        for (k, val) in &v.float_props {
+            parts.push(format!("{}={}", k, val));
+        }
+        parts.join(", ")
+    }
 }
 
 impl Display for Graph {
@@ -109,10 +886,10 @@ impl Display for Graph {
             graph.push_str("UndirectedGraph(");
         }
 
-        for v in &self.vertices {
+        for v in self.vertices.iter().flatten() {
             let mut s = format!("{} => [", v.id);
 
-            for n in self.get_adjacent_vertices(v.id).unwrap() {
+            for n in self.neighbors(v.id) {
                 s.push_str(format!("{}, ", n).as_str())
             }
             s.push_str("],\n");
@@ -126,7 +903,7 @@ impl Display for Graph {
 
 #[cfg(test)]
 mod tests {
-    use super::{Vertex, Graph};
+    use super::{Vertex, Graph, GraphError};
 
     #[test]
     fn can_make_vertices() {
@@ -153,8 +930,8 @@ mod tests {
         assert_eq!(undirected.num_vertices(), 2);
         assert_eq!(undirected.num_edges(), 2);
         assert_eq!(undirected.get_vertex(v2).unwrap().id, v2);
-        assert_eq!(undirected.adjacent[v1][0], v2);
-        assert_eq!(undirected.adjacent[v2][0], v1);
+        assert_eq!(undirected.neighbors(v1)[0], v2);
+        assert_eq!(undirected.neighbors(v2)[0], v1);
     }
 
     #[test]
@@ -166,7 +943,294 @@ mod tests {
         assert_eq!(directed.num_vertices(), 2);
         assert_eq!(directed.num_edges(), 1);
         assert_eq!(directed.get_vertex(v2).unwrap().id, v2);
-        assert_eq!(directed.adjacent[v1][0], v2);
+        assert_eq!(directed.neighbors(v1)[0], v2);
         assert!(directed.adjacent[v2].is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn can_add_weighted_edges() {
+        let mut undirected = Graph::new(false);
+        let v1 = undirected.add_vertex();
+        let v2 = undirected.add_vertex();
+        let e = undirected.add_weighted_edge(v1, v2, 2.5);
+        assert_eq!(undirected.get_edge(e).unwrap().weight, 2.5);
+        assert_eq!(undirected.edge_weight(v1, v2), Some(2.5));
+        assert_eq!(undirected.edge_weight(v2, v1), Some(2.5));
+    }
+
+    #[test]
+    fn finds_shortest_path() {
+        let mut g = Graph::new(true);
+        let a = g.add_vertex();
+        let b = g.add_vertex();
+        let c = g.add_vertex();
+        g.add_weighted_edge(a, b, 1.0);
+        g.add_weighted_edge(b, c, 2.0);
+        g.add_weighted_edge(a, c, 10.0);
+
+        let dist = g.shortest_paths(a).unwrap();
+        assert_eq!(dist[&c], 3.0);
+
+        let (path, cost) = g.shortest_path(a, c).unwrap().unwrap();
+        assert_eq!(path, vec![a, b, c]);
+        assert_eq!(cost, 3.0);
+    }
+
+    #[test]
+    fn astar_matches_dijkstra_with_zero_heuristic() {
+        let mut g = Graph::new(true);
+        let a = g.add_vertex();
+        let b = g.add_vertex();
+        let c = g.add_vertex();
+        g.add_weighted_edge(a, b, 1.0);
+        g.add_weighted_edge(b, c, 2.0);
+
+        let (path, cost) = g.astar(a, c, |_| 0.0).unwrap().unwrap();
+        assert_eq!(path, vec![a, b, c]);
+        assert_eq!(cost, 3.0);
+    }
+
+    #[test]
+    fn astar_honors_nonzero_heuristic() {
+        let mut g = Graph::new(true);
+        for _ in 0..3 {
+            g.add_vertex();
+        }
+        g.add_weighted_edge(0, 1, 1.0);
+        g.add_weighted_edge(1, 2, 1.0);
+        g.add_weighted_edge(0, 2, 10.0);
+
+        // an admissible, consistent heuristic must not drop the cheap path
+        let h = |v: usize| (2 - v as i64).max(0) as f64;
+        let (path, cost) = g.astar(0, 2, h).unwrap().unwrap();
+        assert_eq!(path, vec![0, 1, 2]);
+        assert_eq!(cost, 2.0);
+    }
+
+    #[test]
+    fn rejects_negative_weights() {
+        let mut g = Graph::new(true);
+        let a = g.add_vertex();
+        let b = g.add_vertex();
+        g.add_weighted_edge(a, b, -1.0);
+        assert_eq!(g.shortest_paths(a), Err(GraphError::NegativeWeight));
+    }
+
+    #[test]
+    fn traverses_breadth_and_depth_first() {
+        let mut g = Graph::new(true);
+        let a = g.add_vertex();
+        let b = g.add_vertex();
+        let c = g.add_vertex();
+        g.add_edge(a, b);
+        g.add_edge(a, c);
+        g.add_edge(b, c);
+        assert_eq!(g.bfs(a), vec![a, b, c]);
+        assert_eq!(g.dfs(a), vec![a, b, c]);
+    }
+
+    #[test]
+    fn detects_cycles_and_orders_dags() {
+        let mut dag = Graph::new(true);
+        let a = dag.add_vertex();
+        let b = dag.add_vertex();
+        let c = dag.add_vertex();
+        dag.add_edge(a, b);
+        dag.add_edge(b, c);
+        assert!(!dag.is_cyclic());
+        assert_eq!(dag.topological_sort(), Some(vec![a, b, c]));
+
+        dag.add_edge(c, a);
+        assert!(dag.is_cyclic());
+        assert_eq!(dag.topological_sort(), None);
+    }
+
+    #[test]
+    fn exports_dot() {
+        let mut directed = Graph::new(true);
+        let a = directed.add_vertex();
+        let b = directed.add_vertex();
+        directed.add_weighted_edge(a, b, 3.0);
+        let dot = directed.to_dot();
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("0 -> 1 [label=\"3\"];"));
+
+        let undirected = {
+            let mut g = Graph::new(false);
+            let x = g.add_vertex();
+            let y = g.add_vertex();
+            g.add_edge(x, y);
+            g
+        };
+        assert!(undirected.to_dot().contains("0 -- 1"));
+    }
+
+    #[test]
+    fn dot_escapes_quotes_in_labels() {
+        let mut g = Graph::new(true);
+        let a = g.add_vertex();
+        g.vertices[a]
+            .as_mut()
+            .unwrap()
+            .add_string_props("name".to_string(), "a\"b".to_string());
+        assert!(g.to_dot().contains("label=\"name=a\\\"b\""));
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let mut g = Graph::new(true);
+        let a = g.add_vertex();
+        let b = g.add_vertex();
+        g.vertices[a].as_mut().unwrap().add_string_props("name".to_string(), "a".to_string());
+        let e = g.add_weighted_edge(a, b, 4.0);
+        g.edges[e].add_int_props("capacity".to_string(), 7);
+
+        let bytes = g.to_bytes();
+        let back = Graph::from_bytes(&bytes).unwrap();
+        assert_eq!(back.num_vertices(), 2);
+        assert_eq!(back.edges.len(), 1);
+        assert!(back.is_directed);
+        assert_eq!(back.get_vertex(a).unwrap().string_props["name"], "a");
+        assert_eq!(back.edge_weight(a, b), Some(4.0));
+        assert_eq!(back.get_edge(e).unwrap().int_props["capacity"], 7);
+    }
+
+    #[test]
+    fn round_trips_with_tombstones() {
+        let mut g = Graph::new(false);
+        let a = g.add_vertex();
+        let _b = g.add_vertex();
+        let c = g.add_vertex();
+        g.add_edge(a, c);
+        g.remove_vertex(_b);
+
+        let back = Graph::from_bytes(&g.to_bytes()).unwrap();
+        assert_eq!(back.num_vertices(), 2);
+        assert!(back.get_vertex(_b).is_none());
+        assert_eq!(back.edge_weight(a, c), Some(1.0));
+        // the tombstoned slot is still reusable after a reload
+        assert_eq!(back.free_list, vec![_b]);
+    }
+
+    #[test]
+    fn rejects_out_of_range_edge() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0x7ae7_1ffdu32.to_le_bytes()); // magic
+        buf.extend_from_slice(&1u32.to_le_bytes()); // version
+        buf.extend_from_slice(&1u64.to_le_bytes()); // capacity
+        buf.extend_from_slice(&1u64.to_le_bytes()); // edge count
+        buf.push(1); // is_directed
+        // one present vertex, id 0, no properties
+        buf.push(1);
+        buf.extend_from_slice(&0u64.to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes());
+        // one edge pointing at a non-existent vertex 9
+        buf.extend_from_slice(&0u64.to_le_bytes());
+        buf.extend_from_slice(&9u64.to_le_bytes());
+        buf.extend_from_slice(&1.0f64.to_bits().to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes());
+
+        assert_eq!(Graph::from_bytes(&buf).err(), Some(GraphError::InvalidVertex(9)));
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let bytes = vec![0u8; 32];
+        match Graph::from_bytes(&bytes) {
+            Err(GraphError::BadMagic(_)) => {}
+            other => panic!("expected BadMagic, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn builds_csr() {
+        let mut g = Graph::new(true);
+        let a = g.add_vertex();
+        let b = g.add_vertex();
+        let c = g.add_vertex();
+        g.add_weighted_edge(a, b, 1.0);
+        g.add_weighted_edge(a, c, 2.0);
+        let csr = g.to_csr();
+        assert_eq!(csr.n, 3);
+        assert_eq!(csr.row_ptr, vec![0, 2, 2, 2]);
+        assert_eq!(csr.col_idx, vec![b, c]);
+        assert_eq!(csr.values, vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn compresses_dense_blocks() {
+        let mut g = Graph::new(true);
+        for _ in 0..2 {
+            g.add_vertex();
+        }
+        // fully connect the single 2x2 block
+        for i in 0..2 {
+            for j in 0..2 {
+                g.add_edge(i, j);
+            }
+        }
+        let compressed = g.compress(2, 0.5);
+        assert_eq!(compressed.blocks.len(), 1);
+        assert_eq!(compressed.blocks[0].density, 1.0);
+
+        let back = compressed.decompress();
+        assert_eq!(back.num_vertices(), 2);
+        assert_eq!(back.edge_weight(0, 1), Some(1.0));
+    }
+
+    #[test]
+    fn boundary_block_density_uses_actual_cells() {
+        let mut g = Graph::new(true);
+        for _ in 0..3 {
+            g.add_vertex();
+        }
+        // single edge lands in the 1x1 corner block (rows 2..3, cols 2..3)
+        g.add_edge(2, 2);
+        let compressed = g.compress(2, 1.0);
+        assert_eq!(compressed.blocks.len(), 1);
+        assert_eq!(compressed.blocks[0].row, 1);
+        assert_eq!(compressed.blocks[0].col, 1);
+        assert_eq!(compressed.blocks[0].density, 1.0);
+    }
+
+    #[test]
+    fn removes_edges() {
+        let mut g = Graph::new(false);
+        let a = g.add_vertex();
+        let b = g.add_vertex();
+        let c = g.add_vertex();
+        g.add_edge(a, b);
+        g.add_edge(b, c);
+        g.remove_edge(a, b);
+        assert_eq!(g.edge_weight(a, b), None);
+        assert_eq!(g.edge_weight(b, c), Some(1.0));
+        assert_eq!(g.num_edges(), 2); // one undirected edge, both directions
+    }
+
+    #[test]
+    fn removes_vertices_and_reuses_slots() {
+        let mut g = Graph::new(true);
+        let a = g.add_vertex();
+        let b = g.add_vertex();
+        let c = g.add_vertex();
+        g.add_edge(a, b);
+        g.add_edge(b, c);
+
+        g.remove_vertex(b);
+        assert_eq!(g.num_vertices(), 2);
+        assert_eq!(g.num_edges(), 0); // both incident edges pruned
+        assert!(g.get_vertex(b).is_none());
+        // a and c keep their original ids
+        assert_eq!(g.get_vertex(a).unwrap().id, a);
+        assert_eq!(g.get_vertex(c).unwrap().id, c);
+
+        // the freed slot is reused on the next insert
+        let d = g.add_vertex();
+        assert_eq!(d, b);
+        assert_eq!(g.num_vertices(), 3);
+    }
+}